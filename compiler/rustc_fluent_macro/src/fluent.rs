@@ -0,0 +1,398 @@
+//! Implementation of the `fluent_messages` macro.
+//!
+//! This macro is an internal detail of the compiler's diagnostic machinery: it parses the
+//! crate's `.ftl` Fluent resources at compile time and generates a `fluent_generated` module of
+//! `DiagnosticMessage` constants, so that diagnostic definitions never have to spell out message
+//! identifiers as string literals.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use fluent_syntax::ast::{
+    Attribute as FluentAttribute, Entry, Expression, InlineExpression, Message, Pattern,
+    PatternElement,
+};
+use fluent_syntax::parser::parse as parse_ftl;
+use proc_macro::{Diagnostic, Level, TokenStream};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, LitStr, Token};
+
+/// The input to `fluent_messages!` is a comma-separated list of string literal paths, each
+/// relative to the directory of the source file containing the invocation, e.g.
+/// `fluent_messages! { "./typeck.ftl" }`.
+/// A path containing a glob character (`*`) is expanded to every `.ftl` file it matches, so a
+/// crate can split its messages across many topical files with a single invocation, e.g.
+/// `fluent_messages! { "./messages/*.ftl" }`.
+struct Resources {
+    paths: Punctuated<LitStr, Token![,]>,
+}
+
+impl Parse for Resources {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        Ok(Resources {
+            paths: Punctuated::parse_terminated(input)?,
+        })
+    }
+}
+
+/// A single `.ftl` file resolved from a (possibly globbed) macro argument, paired with the
+/// literal whose span diagnostics about it should point at and the module name its generated
+/// constants will be namespaced under (its file stem).
+struct ResourceFile {
+    source: LitStr,
+    absolute_path: PathBuf,
+    module: Ident,
+}
+
+/// Expands a single `fluent_messages!` argument into the `.ftl` files it refers to, resolving
+/// globs relative to `base_dir`. Each resolved file is namespaced in `fluent_generated` by
+/// its file stem, so glob matches must not collide on stem.
+fn resolve_resource_files(base_dir: &Path, path: &LitStr) -> Vec<ResourceFile> {
+    let relative_path = path.value();
+
+    if !relative_path.contains('*') {
+        let absolute_path = base_dir.join(&relative_path);
+        let module = file_stem_ident(path, &absolute_path);
+        return module
+            .into_iter()
+            .map(|module| ResourceFile {
+                source: path.clone(),
+                absolute_path: absolute_path.clone(),
+                module,
+            })
+            .collect();
+    }
+
+    let pattern = base_dir.join(&relative_path);
+    let matches = match glob::glob(&pattern.to_string_lossy()) {
+        Ok(matches) => matches,
+        Err(e) => {
+            Diagnostic::spanned(
+                path.span().unwrap(),
+                Level::Error,
+                format!("invalid glob pattern {relative_path:?}: {e}"),
+            )
+            .emit();
+            return Vec::new();
+        }
+    };
+
+    let mut files: Vec<_> = matches
+        .filter_map(|entry| match entry {
+            Ok(absolute_path) => file_stem_ident(path, &absolute_path).map(|module| ResourceFile {
+                source: path.clone(),
+                absolute_path,
+                module,
+            }),
+            Err(e) => {
+                Diagnostic::spanned(
+                    path.span().unwrap(),
+                    Level::Error,
+                    format!("failed to read glob entry for {relative_path:?}: {e}"),
+                )
+                .emit();
+                None
+            }
+        })
+        .collect();
+    // `glob` does not guarantee an order; sort so the generated code (and any diagnostics about
+    // it) are stable across compilations.
+    files.sort_by(|a, b| a.absolute_path.cmp(&b.absolute_path));
+    files
+}
+
+fn file_stem_ident(source: &LitStr, path: &Path) -> Option<Ident> {
+    let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+        Diagnostic::spanned(
+            source.span().unwrap(),
+            Level::Error,
+            format!("could not determine a module name for {path:?}"),
+        )
+        .emit();
+        return None;
+    };
+
+    // File stems are not guaranteed to be valid Rust identifiers (e.g. `lexer-errors.ftl` or
+    // `001-errors.ftl`), but they need to be since they become `fluent_generated` module names.
+    match syn::parse_str::<Ident>(stem) {
+        Ok(ident) => Some(ident),
+        Err(e) => {
+            Diagnostic::spanned(
+                source.span().unwrap(),
+                Level::Error,
+                format!("file stem `{stem}` of {path:?} is not a valid module name: {e}"),
+            )
+            .emit();
+            None
+        }
+    }
+}
+
+/// The variable names referenced by a single Fluent message or one of its attributes, collected
+/// by walking the parsed pattern for `{$ident}` placeables (recursing into `{$n ->}` selectors).
+fn pattern_variables(pattern: &Pattern<String>, vars: &mut BTreeSet<String>) {
+    for elem in &pattern.elements {
+        match elem {
+            PatternElement::TextElement { .. } => {}
+            PatternElement::Placeable { expression } => expression_variables(expression, vars),
+        }
+    }
+}
+
+fn expression_variables(expression: &Expression<String>, vars: &mut BTreeSet<String>) {
+    match expression {
+        Expression::Inline(inline) => inline_variables(inline, vars),
+        Expression::Select { selector, variants } => {
+            inline_variables(selector, vars);
+            for variant in variants {
+                pattern_variables(&variant.value, vars);
+            }
+        }
+    }
+}
+
+fn inline_variables(inline: &InlineExpression<String>, vars: &mut BTreeSet<String>) {
+    match inline {
+        InlineExpression::VariableReference { id } => {
+            vars.insert(id.name.clone());
+        }
+        InlineExpression::FunctionReference { arguments, .. } => {
+            for arg in &arguments.positional {
+                expression_variables(arg, vars);
+            }
+            for arg in &arguments.named {
+                inline_variables(&arg.value, vars);
+            }
+        }
+        // Like a function reference, but the parenthesized argument list is optional, e.g.
+        // `{-brand}` vs. `{-brand(case: "accusative")}`.
+        InlineExpression::TermReference { arguments, .. } => {
+            if let Some(arguments) = arguments {
+                for arg in &arguments.positional {
+                    expression_variables(arg, vars);
+                }
+                for arg in &arguments.named {
+                    inline_variables(&arg.value, vars);
+                }
+            }
+        }
+        InlineExpression::Placeable { expression } => expression_variables(expression, vars),
+        _ => {}
+    }
+}
+
+/// All variables required by a message, gathered from its value and every attribute.
+fn message_variables(message: &Message<String>) -> BTreeSet<String> {
+    let mut vars = BTreeSet::new();
+    if let Some(pattern) = &message.value {
+        pattern_variables(pattern, &mut vars);
+    }
+    for FluentAttribute { value, .. } in &message.attributes {
+        pattern_variables(value, &mut vars);
+    }
+    vars
+}
+
+pub(crate) fn fluent_messages(input: TokenStream) -> TokenStream {
+    let resources = syn::parse_macro_input!(input as Resources);
+
+    // Message identifiers must be unique across every merged file, not just within one, so this
+    // is tracked globally rather than being reset per-file.
+    let mut seen_messages: HashMap<String, proc_macro::Span> = HashMap::new();
+    // Likewise, `fluent_generated` module names (derived from file stems) must be unique across
+    // every file resolved from every argument, since a glob can match files with the same stem in
+    // different directories and two separate arguments can name the same stem outright.
+    let mut seen_modules: HashMap<String, proc_macro::Span> = HashMap::new();
+    let mut modules = Vec::new();
+    let mut resource_paths = Vec::new();
+
+    for path in &resources.paths {
+        // Resolve against the directory of the invoking source file, made absolute (relative
+        // source paths are relative to the working directory) so that the `include_str!` below
+        // finds the same file.
+        let source_file = path.span().unwrap().source_file().path();
+        let mut base_dir = std::env::current_dir().unwrap().join(source_file);
+        base_dir.pop();
+        for file in resolve_resource_files(&base_dir, path) {
+            let ResourceFile {
+                source,
+                absolute_path,
+                module,
+            } = file;
+
+            let module_name = module.to_string();
+            if let Some(&prev_span) = seen_modules.get(&module_name) {
+                Diagnostic::spanned(
+                    source.span().unwrap(),
+                    Level::Error,
+                    format!(
+                        "resource file stem `{module_name}` collides with another resolved \
+                         resource; `fluent_generated` module names must be unique"
+                    ),
+                )
+                .span_note(prev_span, "previously resolved here")
+                .emit();
+                continue;
+            }
+            seen_modules.insert(module_name, source.span().unwrap());
+
+            let contents = match std::fs::read_to_string(&absolute_path) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    Diagnostic::spanned(
+                        source.span().unwrap(),
+                        Level::Error,
+                        format!("could not open Fluent resource {absolute_path:?}: {e}"),
+                    )
+                    .emit();
+                    continue;
+                }
+            };
+
+            let resource = match parse_ftl(contents) {
+                Ok(resource) => resource,
+                Err((_, errs)) => {
+                    for err in errs {
+                        Diagnostic::spanned(
+                            source.span().unwrap(),
+                            Level::Error,
+                            format!("failed to parse Fluent resource {absolute_path:?}: {err}"),
+                        )
+                        .emit();
+                    }
+                    continue;
+                }
+            };
+
+            let mut constants = Vec::new();
+
+            for entry in resource.body {
+                let Entry::Message(message) = entry else {
+                    continue;
+                };
+                let name = message.id.name.clone();
+
+                if let Some(&prev_span) = seen_messages.get(&name) {
+                    Diagnostic::spanned(
+                        source.span().unwrap(),
+                        Level::Error,
+                        format!("message `{name}` is already defined"),
+                    )
+                    .span_note(prev_span, "previously defined here")
+                    .emit();
+                    continue;
+                }
+                seen_messages.insert(name.clone(), source.span().unwrap());
+
+                let vars = message_variables(&message);
+                let const_ident = Ident::new(&name, proc_macro2::Span::call_site());
+                let args_ident = Ident::new(
+                    &format!("{}_ARGS", name.to_uppercase()),
+                    proc_macro2::Span::call_site(),
+                );
+                let var_lits = vars.iter().map(|v| v.as_str());
+
+                constants.push(quote! {
+                    #[allow(non_upper_case_globals)]
+                    pub const #const_ident: crate::DiagnosticMessage =
+                        crate::DiagnosticMessage::fluent(#name);
+
+                    #[allow(non_upper_case_globals)]
+                    pub const #args_ident: &[&str] = &[#(#var_lits),*];
+                });
+
+                for attr in &message.attributes {
+                    let attr_name = format!("{name}_{}", attr.id.name);
+                    let attr_const_ident = Ident::new(&attr_name, proc_macro2::Span::call_site());
+                    let attr_id = &attr.id.name;
+                    constants.push(quote! {
+                        #[allow(non_upper_case_globals)]
+                        pub const #attr_const_ident: crate::DiagnosticMessage =
+                            crate::DiagnosticMessage::fluent_attr(#name, #attr_id);
+                    });
+                }
+            }
+
+            let absolute_path_str = absolute_path.to_string_lossy().into_owned();
+            modules.push(quote! {
+                #[allow(unused)]
+                pub mod #module {
+                    #(#constants)*
+                }
+            });
+            resource_paths.push(absolute_path_str);
+        }
+    }
+
+    let output: TokenStream2 = quote! {
+        pub static DEFAULT_LOCALE_RESOURCE: &[&str] = &[#(include_str!(#resource_paths)),*];
+
+        #[allow(unused)]
+        mod fluent_generated {
+            #(#modules)*
+        }
+    };
+
+    output.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables_of(ftl: &str) -> BTreeSet<String> {
+        let resource = match parse_ftl(ftl.to_string()) {
+            Ok(resource) => resource,
+            Err((_, errs)) => panic!("failed to parse test fixture: {errs:?}"),
+        };
+        let message = resource
+            .body
+            .into_iter()
+            .find_map(|entry| match entry {
+                Entry::Message(message) => Some(message),
+                _ => None,
+            })
+            .expect("fixture must contain a message");
+        message_variables(&message)
+    }
+
+    fn vars(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn collects_a_plain_variable_reference() {
+        assert_eq!(variables_of("foo = a { $bar } thing\n"), vars(&["bar"]));
+    }
+
+    #[test]
+    fn collects_variables_from_attributes() {
+        assert_eq!(variables_of("foo = a thing\n    .label = { $x }\n"), vars(&["x"]));
+    }
+
+    #[test]
+    fn collects_the_selector_and_variant_variables_of_a_plural_form() {
+        let ftl = "foo = { $count ->\n    [one] one thing\n   *[other] { $count } things\n}\n";
+        assert_eq!(variables_of(ftl), vars(&["count"]));
+    }
+
+    #[test]
+    fn collects_positional_function_arguments_alongside_a_named_one() {
+        // Fluent only allows literals as named argument values, so only `$n` is a variable here.
+        let ftl = "foo = { NUMBER($n, minimumFractionDigits: 2) }\n";
+        assert_eq!(variables_of(ftl), vars(&["n"]));
+    }
+
+    #[test]
+    fn collects_term_reference_arguments() {
+        assert_eq!(variables_of("foo = { -bar($thing) }\n"), vars(&["thing"]));
+    }
+
+    #[test]
+    fn a_term_reference_without_arguments_does_not_panic() {
+        assert_eq!(variables_of("foo = { -bar }\n"), BTreeSet::new());
+    }
+}
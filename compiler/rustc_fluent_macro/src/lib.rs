@@ -14,7 +14,18 @@ mod fluent;
 
 /// Implements the `fluent_messages` macro, which performs compile-time validation of the
 /// compiler's Fluent resources (i.e. that the resources parse and don't multiply define the same
-/// messages) and generates constants that make using those messages in diagnostics more ergonomic.
+/// messages) and generates constants that make using those messages in diagnostics more
+/// ergonomic. For every message, a sibling `<NAME>_ARGS: &[&str]` constant (the message id in
+/// upper case) is also emitted; attribute constants get none of their own. It lists, in sorted
+/// order, the `$var` names the message references (including those nested in
+/// `.label`/attribute sub-messages and `{$n ->}` selectors), so that `#[derive(Diagnostic)]` can
+/// check at compile time that a struct actually supplies every variable its message needs.
+///
+/// The macro accepts more than one resource path, and a path containing a glob (`*`) is expanded
+/// to every `.ftl` file it matches, e.g. `fluent_messages! { "./messages/*.ftl" }`. All matched
+/// files are merged into a single `DEFAULT_LOCALE_RESOURCE` slice, with their constants still
+/// namespaced per file (by file stem) under `fluent_generated`, and message identifiers are
+/// required to be unique across every merged file, not just within one.
 ///
 /// For example, given the following invocation of the macro..
 ///
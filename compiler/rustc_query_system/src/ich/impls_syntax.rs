@@ -70,9 +70,13 @@ impl<'a> HashStable<StableHashingContext<'a>> for SourceFile {
             start_pos: _,
             source_len: _,
             lines: _,
-            ref multibyte_chars,
-            ref non_narrow_chars,
-            ref normalized_pos,
+            multibyte_chars: _,
+            non_narrow_chars: _,
+            normalized_pos: _,
+            // `position_tables_hash` is an internal cache of the four position arrays above,
+            // private to `rustc_span` (hence the `..`); its value is folded into the hash via the
+            // method call below.
+            ..
         } = *self;
 
         name_hash.hash_stable(hcx, hasher);
@@ -82,29 +86,15 @@ impl<'a> HashStable<StableHashingContext<'a>> for SourceFile {
         {
             // We are always in `Lines` form by the time we reach here.
             assert!(self.lines.read().is_lines());
-            let lines = self.lines();
-            // We only hash the relative position within this source_file
-            lines.len().hash_stable(hcx, hasher);
-            for &line in lines.iter() {
-                line.hash_stable(hcx, hasher);
-            }
         }
 
-        // We only hash the relative position within this source_file
-        multibyte_chars.len().hash_stable(hcx, hasher);
-        for &char_pos in multibyte_chars.iter() {
-            char_pos.hash_stable(hcx, hasher);
-        }
-
-        non_narrow_chars.len().hash_stable(hcx, hasher);
-        for &char_pos in non_narrow_chars.iter() {
-            char_pos.hash_stable(hcx, hasher);
-        }
-
-        normalized_pos.len().hash_stable(hcx, hasher);
-        for &char_pos in normalized_pos.iter() {
-            char_pos.hash_stable(hcx, hasher);
-        }
+        // `lines`, `multibyte_chars`, `non_narrow_chars` and `normalized_pos` are all vectors of
+        // monotonically increasing relative positions, so they only ever grow by appending to the
+        // end. Rather than walking all four element-by-element on every `hash_stable` call (which
+        // showed up as a hot path when the same `SourceFile` is hashed by many incremental
+        // queries), we fold them into a single composite hash once and cache it, guarded the same
+        // way as the `Lines` form assertion above.
+        self.stable_position_tables_hash(hcx).hash_stable(hcx, hasher);
 
         cnum.hash_stable(hcx, hasher);
     }
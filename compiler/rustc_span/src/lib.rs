@@ -0,0 +1,240 @@
+//! Excerpt of `rustc_span`.
+//!
+//! This snapshot only includes the parts of `SourceFile` needed by
+//! `rustc_query_system`'s `HashStable` impl for it; the rest of the crate
+//! (interning, `Span`, `SyntaxContext`, etc.) is not part of this tree.
+
+use std::sync::{OnceLock, RwLock as StdRwLock, RwLockReadGuard};
+
+use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
+
+/// A non-poisoning wrapper mirroring `rustc_data_structures::sync::RwLock`, which the full crate
+/// uses in place of `std::sync::RwLock`: a panic while the lock is held for writing does not make
+/// later accesses fail, since poisoning is ignored.
+pub struct RwLock<T>(StdRwLock<T>);
+
+impl<T> RwLock<T> {
+    fn new(value: T) -> Self {
+        RwLock(StdRwLock::new(value))
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.0.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, T> {
+        self.0.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A byte position relative to the start of a `SourceFile`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RelativeBytePos(pub u32);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct MultiByteChar {
+    pub pos: RelativeBytePos,
+    pub bytes: u8,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct NonNarrowChar {
+    pub pos: RelativeBytePos,
+    pub width: u8,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct NormalizedPos {
+    pub pos: RelativeBytePos,
+    pub diff: u32,
+}
+
+impl<CTX> HashStable<CTX> for RelativeBytePos {
+    fn hash_stable(&self, hcx: &mut CTX, hasher: &mut StableHasher) {
+        self.0.hash_stable(hcx, hasher);
+    }
+}
+
+impl<CTX> HashStable<CTX> for MultiByteChar {
+    fn hash_stable(&self, hcx: &mut CTX, hasher: &mut StableHasher) {
+        let MultiByteChar { pos, bytes } = *self;
+        pos.hash_stable(hcx, hasher);
+        bytes.hash_stable(hcx, hasher);
+    }
+}
+
+impl<CTX> HashStable<CTX> for NonNarrowChar {
+    fn hash_stable(&self, hcx: &mut CTX, hasher: &mut StableHasher) {
+        let NonNarrowChar { pos, width } = *self;
+        pos.hash_stable(hcx, hasher);
+        width.hash_stable(hcx, hasher);
+    }
+}
+
+impl<CTX> HashStable<CTX> for NormalizedPos {
+    fn hash_stable(&self, hcx: &mut CTX, hasher: &mut StableHasher) {
+        let NormalizedPos { pos, diff } = *self;
+        pos.hash_stable(hcx, hasher);
+        diff.hash_stable(hcx, hasher);
+    }
+}
+
+/// Whether a `SourceFile`'s line table is in its normal, queryable form. Other representations
+/// (e.g. a diff against another file's lines, used transiently while building a `SourceMap`) are
+/// not relevant to this excerpt.
+pub enum SourceFileLines {
+    Lines(Vec<RelativeBytePos>),
+}
+
+impl SourceFileLines {
+    pub fn is_lines(&self) -> bool {
+        matches!(self, SourceFileLines::Lines(_))
+    }
+}
+
+pub struct CrateNum(pub u32);
+pub struct FileName(pub String);
+pub struct SourceFileHash(pub u64);
+pub struct BytePos(pub u32);
+
+pub struct SourceFile {
+    pub name: FileName,
+    pub name_hash: u64,
+    pub cnum: CrateNum,
+    pub src: Option<std::sync::Arc<String>>,
+    pub src_hash: SourceFileHash,
+    pub external_src: (),
+    pub start_pos: BytePos,
+    pub source_len: RelativeBytePos,
+    pub lines: RwLock<SourceFileLines>,
+    pub multibyte_chars: Vec<MultiByteChar>,
+    pub non_narrow_chars: Vec<NonNarrowChar>,
+    pub normalized_pos: Vec<NormalizedPos>,
+    /// Cached, folded hash of `lines`, `multibyte_chars`, `non_narrow_chars` and
+    /// `normalized_pos`, computed lazily on first use by [`Self::stable_position_tables_hash`].
+    /// These four arrays only ever grow by appending monotonically increasing positions, so the
+    /// cache stays valid for the lifetime of a given line table and is only cleared by
+    /// [`Self::invalidate_position_tables_hash`], which every mutator of the line table must call.
+    position_tables_hash: OnceLock<u64>,
+}
+
+impl SourceFile {
+    pub fn lines(&self) -> RwLockReadGuard<'_, SourceFileLines> {
+        self.lines.read()
+    }
+
+    /// Rebuilds the line table, e.g. after re-lexing a `SourceFile` whose line starts were not
+    /// known up front. The table is always stored in `Lines` form, so the `Lines` form assertion
+    /// in `HashStable` holds afterwards.
+    pub fn set_lines(&mut self, lines: Vec<RelativeBytePos>) {
+        *self.lines.write() = SourceFileLines::Lines(lines);
+        self.invalidate_position_tables_hash();
+    }
+
+    /// A single hash folding together `lines`, `multibyte_chars`, `non_narrow_chars` and
+    /// `normalized_pos`, computed once and cached rather than walked element-by-element on every
+    /// `hash_stable` call, which is expensive for large files hashed repeatedly across
+    /// incremental compilation queries. Like every other field hashed by `HashStable`, this goes
+    /// through `StableHasher` (not a host-dependent general-purpose hasher), so the result stays
+    /// portable across compilation sessions and target architectures.
+    pub fn stable_position_tables_hash<CTX>(&self, hcx: &mut CTX) -> u64 {
+        *self.position_tables_hash.get_or_init(|| self.compute_position_tables_hash(hcx))
+    }
+
+    fn compute_position_tables_hash<CTX>(&self, hcx: &mut CTX) -> u64 {
+        // We are always in `Lines` form by the time this is called.
+        let guard = self.lines.read();
+        assert!(guard.is_lines());
+
+        let mut hasher = StableHasher::new();
+        let SourceFileLines::Lines(lines) = &*guard;
+        lines.len().hash_stable(hcx, &mut hasher);
+        for line in lines {
+            line.hash_stable(hcx, &mut hasher);
+        }
+
+        self.multibyte_chars.len().hash_stable(hcx, &mut hasher);
+        for char_pos in &self.multibyte_chars {
+            char_pos.hash_stable(hcx, &mut hasher);
+        }
+
+        self.non_narrow_chars.len().hash_stable(hcx, &mut hasher);
+        for char_pos in &self.non_narrow_chars {
+            char_pos.hash_stable(hcx, &mut hasher);
+        }
+
+        self.normalized_pos.len().hash_stable(hcx, &mut hasher);
+        for char_pos in &self.normalized_pos {
+            char_pos.hash_stable(hcx, &mut hasher);
+        }
+
+        hasher.finish()
+    }
+
+    /// Must be called whenever `lines`, `multibyte_chars`, `non_narrow_chars` or `normalized_pos`
+    /// are mutated in place, so a later `stable_position_tables_hash` call recomputes the hash
+    /// instead of returning a stale cached value.
+    fn invalidate_position_tables_hash(&mut self) {
+        self.position_tables_hash = OnceLock::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_file(lines: &[u32], multibyte: &[(u32, u8)]) -> SourceFile {
+        SourceFile {
+            name: FileName(String::new()),
+            name_hash: 0,
+            cnum: CrateNum(0),
+            src: None,
+            src_hash: SourceFileHash(0),
+            external_src: (),
+            start_pos: BytePos(0),
+            source_len: RelativeBytePos(0),
+            lines: RwLock::new(SourceFileLines::Lines(
+                lines.iter().map(|&pos| RelativeBytePos(pos)).collect(),
+            )),
+            multibyte_chars: multibyte
+                .iter()
+                .map(|&(pos, bytes)| MultiByteChar { pos: RelativeBytePos(pos), bytes })
+                .collect(),
+            non_narrow_chars: Vec::new(),
+            normalized_pos: Vec::new(),
+            position_tables_hash: OnceLock::new(),
+        }
+    }
+
+    #[test]
+    fn repeated_calls_return_the_same_cached_hash() {
+        let sf = source_file(&[0, 10, 25], &[(12, 2)]);
+        let first = sf.stable_position_tables_hash(&mut ());
+        let second = sf.stable_position_tables_hash(&mut ());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn cached_hash_matches_a_fresh_recompute_of_the_same_data() {
+        let sf = source_file(&[0, 10, 25], &[(12, 2)]);
+        let cached = sf.stable_position_tables_hash(&mut ());
+        let recomputed = sf.compute_position_tables_hash(&mut ());
+        assert_eq!(cached, recomputed);
+    }
+
+    #[test]
+    fn differing_line_tables_hash_differently() {
+        let a = source_file(&[0, 10, 25], &[]);
+        let b = source_file(&[0, 10, 30], &[]);
+        assert_ne!(a.stable_position_tables_hash(&mut ()), b.stable_position_tables_hash(&mut ()));
+    }
+
+    #[test]
+    fn set_lines_invalidates_the_cached_hash() {
+        let mut sf = source_file(&[0, 10, 25], &[]);
+        let before = sf.stable_position_tables_hash(&mut ());
+        sf.set_lines(vec![RelativeBytePos(0), RelativeBytePos(40)]);
+        let after = sf.stable_position_tables_hash(&mut ());
+        assert_ne!(before, after, "mutating the line table must invalidate the cached hash");
+    }
+}
@@ -0,0 +1,23 @@
+// Two explicit resource paths that happen to share a file stem would otherwise generate two
+// `pub mod errors { .. }` items under `fluent_generated` and surface as an opaque E0428 from the
+// generated code; this must be a clear macro diagnostic instead (gorentbarak/rust#chunk0-2).
+#![feature(rustc_private)]
+
+extern crate rustc_fluent_macro;
+
+pub enum DiagnosticMessage {
+    FluentIdentifier(&'static str, Option<&'static str>),
+}
+
+impl DiagnosticMessage {
+    pub const fn fluent(id: &'static str) -> Self {
+        DiagnosticMessage::FluentIdentifier(id, None)
+    }
+    pub const fn fluent_attr(id: &'static str, attr: &'static str) -> Self {
+        DiagnosticMessage::FluentIdentifier(id, Some(attr))
+    }
+}
+
+rustc_fluent_macro::fluent_messages! { "./colliding-module-names/a/errors.ftl", "./colliding-module-names/b/errors.ftl" } //~ ERROR resource file stem `errors` collides with another resolved resource
+
+fn main() {}
@@ -0,0 +1,22 @@
+// A glob that expands to two files defining the same message id must be rejected, the same as
+// two duplicate messages within a single file would be (gorentbarak/rust#chunk0-2).
+#![feature(rustc_private)]
+
+extern crate rustc_fluent_macro;
+
+pub enum DiagnosticMessage {
+    FluentIdentifier(&'static str, Option<&'static str>),
+}
+
+impl DiagnosticMessage {
+    pub const fn fluent(id: &'static str) -> Self {
+        DiagnosticMessage::FluentIdentifier(id, None)
+    }
+    pub const fn fluent_attr(id: &'static str, attr: &'static str) -> Self {
+        DiagnosticMessage::FluentIdentifier(id, Some(attr))
+    }
+}
+
+rustc_fluent_macro::fluent_messages! { "./glob-duplicate-message/*.ftl" } //~ ERROR message `shared_id` is already defined
+
+fn main() {}
@@ -0,0 +1,34 @@
+// check-pass
+// A glob merges every matched `.ftl` file's messages into one `DEFAULT_LOCALE_RESOURCE`, still
+// namespaced per file stem under `fluent_generated`, with each message's required `$var`s
+// available as an `_ARGS` constant (gorentbarak/rust#chunk0-1, gorentbarak/rust#chunk0-2).
+#![feature(rustc_private)]
+#![allow(dead_code)]
+
+extern crate rustc_fluent_macro;
+
+pub enum DiagnosticMessage {
+    FluentIdentifier(&'static str, Option<&'static str>),
+}
+
+impl DiagnosticMessage {
+    pub const fn fluent(id: &'static str) -> Self {
+        DiagnosticMessage::FluentIdentifier(id, None)
+    }
+    pub const fn fluent_attr(id: &'static str, attr: &'static str) -> Self {
+        DiagnosticMessage::FluentIdentifier(id, Some(attr))
+    }
+}
+
+rustc_fluent_macro::fluent_messages! { "./glob-merges-multiple-files/*.ftl" }
+
+fn uses_generated_constants() {
+    let _: DiagnosticMessage = fluent_generated::typeck::field_multiply_specified;
+    let _: &[&str] = fluent_generated::typeck::FIELD_MULTIPLY_SPECIFIED_ARGS;
+    assert_eq!(fluent_generated::typeck::FIELD_MULTIPLY_SPECIFIED_ARGS, &["ident"]);
+
+    let _: DiagnosticMessage = fluent_generated::borrowck::move_out_of_borrow;
+    assert_eq!(fluent_generated::borrowck::MOVE_OUT_OF_BORROW_ARGS, &["name"]);
+}
+
+fn main() {}
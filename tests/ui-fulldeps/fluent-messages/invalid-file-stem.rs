@@ -0,0 +1,23 @@
+// Glob-matched files are not guaranteed to have identifier-safe stems (hyphens, leading digits
+// are completely normal filenames); this must be a clean diagnostic, not a panic from building an
+// invalid `Ident` (gorentbarak/rust#chunk0-2).
+#![feature(rustc_private)]
+
+extern crate rustc_fluent_macro;
+
+pub enum DiagnosticMessage {
+    FluentIdentifier(&'static str, Option<&'static str>),
+}
+
+impl DiagnosticMessage {
+    pub const fn fluent(id: &'static str) -> Self {
+        DiagnosticMessage::FluentIdentifier(id, None)
+    }
+    pub const fn fluent_attr(id: &'static str, attr: &'static str) -> Self {
+        DiagnosticMessage::FluentIdentifier(id, Some(attr))
+    }
+}
+
+rustc_fluent_macro::fluent_messages! { "./invalid-file-stem/*.ftl" } //~ ERROR is not a valid module name
+
+fn main() {}